@@ -0,0 +1,72 @@
+use std::error::Error;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Ceiling on the exponential backoff so a prolonged outage polls at most
+/// this often instead of drifting towards hours between attempts.
+const MAX_BACKOFF_MS: u128 = 5 * 60 * 1000;
+
+/// Running count of cycle outcomes, so an unattended daemon can report how
+/// it's doing without needing an external metrics sink.
+#[derive(Debug, Default)]
+pub struct CycleCounters {
+    pub successes: u64,
+    pub failures: u64,
+}
+
+/// Full jitter backoff: doubles `base` per failed attempt (capped at
+/// `MAX_BACKOFF_MS`) and returns a random duration in `[half, half*2]` of
+/// that cap, so repeated failures don't all retry in lockstep.
+fn jittered_backoff(base: Duration, attempt: u32) -> Duration {
+    let exp_ms = base.as_millis().saturating_mul(1u128 << attempt.min(10));
+    let capped_ms = exp_ms.min(MAX_BACKOFF_MS).max(1);
+    let half_ms = (capped_ms / 2).max(1);
+    let jitter_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .subsec_millis() as u128
+        % half_ms;
+    Duration::from_millis((half_ms + jitter_ms) as u64)
+}
+
+/// Repeatedly runs `cycle` on `interval`, applying exponential backoff with
+/// jitter whenever it errors (e.g. a `curl::Error` or HTTP failure bubbling
+/// up from `fetch_ztm_data`), and logging a running success/failure count so
+/// the process can be left collecting unattended.
+pub fn run<F>(interval: Duration, mut cycle: F) -> Result<(), Box<dyn Error>>
+where
+    F: FnMut(&mut Option<u64>) -> Result<bool, Box<dyn Error>>,
+{
+    let mut last_feed_timestamp = None;
+    let mut counters = CycleCounters::default();
+    let mut consecutive_failures = 0u32;
+
+    loop {
+        let cycle_start = Instant::now();
+        match cycle(&mut last_feed_timestamp) {
+            Ok(processed) => {
+                counters.successes += 1;
+                consecutive_failures = 0;
+                println!(
+                    "cycle ok in {:?} (processed={processed}), totals: {}/{} succeeded",
+                    cycle_start.elapsed(),
+                    counters.successes,
+                    counters.failures
+                );
+                std::thread::sleep(interval);
+            }
+            Err(e) => {
+                counters.failures += 1;
+                let backoff = jittered_backoff(interval, consecutive_failures);
+                consecutive_failures += 1;
+                eprintln!(
+                    "cycle failed after {:?}: {e}; totals: {}/{} succeeded, backing off {:?}",
+                    cycle_start.elapsed(),
+                    counters.successes,
+                    counters.failures,
+                    backoff
+                );
+                std::thread::sleep(backoff);
+            }
+        }
+    }
+}
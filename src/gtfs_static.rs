@@ -0,0 +1,156 @@
+use std::io::{Cursor, Read};
+use std::sync::Mutex;
+
+use polars::prelude::*;
+use zip::ZipArchive;
+
+/// The static GTFS tables we care about joining onto the realtime frames,
+/// kept around together with the `feed_version` they were parsed from so we
+/// can tell whether a freshly downloaded zip is actually new data.
+pub struct StaticFeed {
+    pub feed_version: String,
+    pub routes_df: DataFrame,
+    pub trips_df: DataFrame,
+    pub stops_df: DataFrame,
+}
+
+impl Clone for StaticFeed {
+    fn clone(&self) -> Self {
+        StaticFeed {
+            feed_version: self.feed_version.clone(),
+            routes_df: self.routes_df.clone(),
+            trips_df: self.trips_df.clone(),
+            stops_df: self.stops_df.clone(),
+        }
+    }
+}
+
+static CACHE: Mutex<Option<StaticFeed>> = Mutex::new(None);
+
+fn read_csv_entry(
+    archive: &mut ZipArchive<Cursor<&[u8]>>,
+    name: &str,
+) -> Result<DataFrame, Box<dyn std::error::Error>> {
+    let mut file = archive.by_name(name)?;
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf)?;
+    Ok(CsvReader::new(Cursor::new(buf))
+        .with_options(CsvReadOptions::default().with_infer_schema_length(None))
+        .finish()?)
+}
+
+/// GTFS IDs (`route_id`, `trip_id`, `stop_id`, ...) are free-form strings per
+/// spec, but Poznań's static feed happens to use all-numeric IDs, so schema
+/// inference reads them as `Int64`. The realtime frames always carry their
+/// IDs as `Utf8`, so without this the join key dtypes mismatch and
+/// `left_join` errors. Cast the given columns to `Utf8` where present.
+fn cast_id_columns_to_string(
+    mut df: DataFrame,
+    columns: &[&str],
+) -> Result<DataFrame, Box<dyn std::error::Error>> {
+    for &name in columns {
+        if let Ok(col) = df.column(name) {
+            let casted = col.clone().cast(&DataType::String)?;
+            df.with_column(casted)?;
+        }
+    }
+    Ok(df)
+}
+
+fn read_feed_version(
+    archive: &mut ZipArchive<Cursor<&[u8]>>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let feed_info_df = read_csv_entry(archive, "feed_info.txt")?;
+    let version = feed_info_df
+        .column("feed_version")?
+        .str()?
+        .get(0)
+        .unwrap_or("unknown")
+        .to_string();
+    Ok(version)
+}
+
+/// Loads the agency's static GTFS zip into Polars DataFrames, reusing the
+/// cached tables when `feed_version` (from `feed_info.txt`) hasn't changed
+/// since the last call so we only reparse the schedule when it's published.
+pub fn load_static_feed(zip_bytes: &[u8]) -> Result<StaticFeed, Box<dyn std::error::Error>> {
+    let mut archive = ZipArchive::new(Cursor::new(zip_bytes))?;
+    let feed_version = read_feed_version(&mut archive)?;
+
+    {
+        let cache = CACHE.lock().unwrap();
+        if let Some(cached) = cache.as_ref() {
+            if cached.feed_version == feed_version {
+                return Ok(cached.clone());
+            }
+        }
+    }
+
+    let routes_df =
+        cast_id_columns_to_string(read_csv_entry(&mut archive, "routes.txt")?, &["route_id"])?;
+    let trips_df = cast_id_columns_to_string(
+        read_csv_entry(&mut archive, "trips.txt")?,
+        &["trip_id", "route_id"],
+    )?;
+    let stops_df =
+        cast_id_columns_to_string(read_csv_entry(&mut archive, "stops.txt")?, &["stop_id"])?;
+
+    let feed = StaticFeed {
+        feed_version,
+        routes_df,
+        trips_df,
+        stops_df,
+    };
+
+    *CACHE.lock().unwrap() = Some(feed.clone());
+
+    Ok(feed)
+}
+
+fn has_column(df: &DataFrame, name: &str) -> bool {
+    df.get_column_names().iter().any(|n| n.as_str() == name)
+}
+
+/// Left-joins `route_short_name`/`route_long_name`/`route_type` (by `route_id`)
+/// and `trip_headsign`/`direction_id` (by `trip_id`) onto any frame that
+/// carries those two key columns, e.g. `vehicle_positions_df` or `trip_updates_df`.
+/// A frame missing a key column (e.g. the `df!("error" => [...])` placeholder
+/// emitted when a `.pb` fails to parse) is passed through untouched instead
+/// of erroring on that join.
+pub fn enrich_with_route_and_trip(mut df: DataFrame, feed: &StaticFeed) -> PolarsResult<DataFrame> {
+    if has_column(&df, "route_id") {
+        let routes = feed.routes_df.select([
+            "route_id",
+            "route_short_name",
+            "route_long_name",
+            "route_type",
+        ])?;
+        df = df.left_join(&routes, ["route_id"], ["route_id"])?;
+    }
+
+    if has_column(&df, "trip_id") {
+        let trips = feed
+            .trips_df
+            .select(["trip_id", "trip_headsign", "direction_id"])?;
+        df = df.left_join(&trips, ["trip_id"], ["trip_id"])?;
+    }
+
+    Ok(df)
+}
+
+/// Left-joins resolved `stop_name`/`stop_lat`/`stop_lon` (by `stop_id`) onto
+/// the long-format stop-time frame, on top of the route/trip enrichment.
+/// Like `enrich_with_route_and_trip`, a frame missing `stop_id` is passed
+/// through untouched.
+pub fn enrich_stop_time_updates(df: DataFrame, feed: &StaticFeed) -> PolarsResult<DataFrame> {
+    let df = enrich_with_route_and_trip(df, feed)?;
+
+    if has_column(&df, "stop_id") {
+        let stops = feed
+            .stops_df
+            .select(["stop_id", "stop_name", "stop_lat", "stop_lon"])?;
+        df.left_join(&stops, ["stop_id"], ["stop_id"])
+    } else {
+        Ok(df)
+    }
+}
@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use polars::prelude::*;
+
+const EARTH_RADIUS_M: f64 = 6371000.0;
+/// Anything faster than this between two snapshots is almost certainly a bad
+/// fix or a vehicle_id reused by a different bus, not a real movement.
+const SUSPECT_JUMP_MPS: f64 = 40.0;
+
+struct Snapshot {
+    lat: f64,
+    lon: f64,
+    timestamp: i64,
+    bearing: Option<f64>,
+}
+
+static PREVIOUS_SNAPSHOTS: Mutex<Option<HashMap<String, Snapshot>>> = Mutex::new(None);
+
+fn haversine_distance_m(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let (phi1, phi2) = (lat1.to_radians(), lat2.to_radians());
+    let d_phi = (lat2 - lat1).to_radians();
+    let d_lambda = (lon2 - lon1).to_radians();
+
+    let a = (d_phi / 2.0).sin().powi(2) + phi1.cos() * phi2.cos() * (d_lambda / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_M * a.sqrt().atan2((1.0 - a).sqrt())
+}
+
+fn bearing_deg(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let (phi1, phi2) = (lat1.to_radians(), lat2.to_radians());
+    let d_lambda = (lon2 - lon1).to_radians();
+
+    let y = d_lambda.sin() * phi2.cos();
+    let x = phi1.cos() * phi2.sin() - phi1.sin() * phi2.cos() * d_lambda.cos();
+    (y.atan2(x).to_degrees() + 360.0) % 360.0
+}
+
+fn has_column(df: &DataFrame, name: &str) -> bool {
+    df.get_column_names().iter().any(|n| n.as_str() == name)
+}
+
+/// Derives `computed_speed_mps`, `computed_bearing_deg` and `suspect_jump`
+/// for each row of `vehicle_positions_df` from the previous snapshot of the
+/// same `vehicle_id`, since the feed's own `speed`/`bearing` fields are
+/// frequently absent. Passes the frame through untouched if it's the
+/// `df!("error" => [...])` placeholder emitted when `vehicle_positions.pb`
+/// failed to parse (no `vehicle_id`/`latitude`/`longitude` columns).
+pub fn compute_motion(df: DataFrame, timestamp: i64) -> PolarsResult<DataFrame> {
+    if !has_column(&df, "vehicle_id")
+        || !has_column(&df, "latitude")
+        || !has_column(&df, "longitude")
+    {
+        return Ok(df);
+    }
+
+    let vehicle_ids = df.column("vehicle_id")?.str()?.clone();
+    let latitudes = df.column("latitude")?.f32()?.clone();
+    let longitudes = df.column("longitude")?.f32()?.clone();
+
+    let mut speeds: Vec<Option<f64>> = Vec::with_capacity(df.height());
+    let mut bearings: Vec<Option<f64>> = Vec::with_capacity(df.height());
+    let mut suspect_jumps: Vec<bool> = Vec::with_capacity(df.height());
+
+    let mut cache_guard = PREVIOUS_SNAPSHOTS.lock().unwrap();
+    let cache = cache_guard.get_or_insert_with(HashMap::new);
+
+    for i in 0..df.height() {
+        let vehicle_id = vehicle_ids.get(i).unwrap_or_default().to_string();
+        let lat = latitudes.get(i).unwrap_or(0.0) as f64;
+        let lon = longitudes.get(i).unwrap_or(0.0) as f64;
+
+        if vehicle_id.is_empty() {
+            speeds.push(None);
+            bearings.push(None);
+            suspect_jumps.push(false);
+            continue;
+        }
+
+        let (speed, bearing, suspect) = match cache.get(&vehicle_id) {
+            Some(prev) => {
+                let dt = (timestamp - prev.timestamp) as f64;
+                if dt <= 0.0 {
+                    (None, None, false)
+                } else if (lat - prev.lat).abs() < f64::EPSILON
+                    && (lon - prev.lon).abs() < f64::EPSILON
+                {
+                    (Some(0.0), prev.bearing, false)
+                } else {
+                    let distance = haversine_distance_m(prev.lat, prev.lon, lat, lon);
+                    let speed = distance / dt;
+                    let bearing = bearing_deg(prev.lat, prev.lon, lat, lon);
+                    (Some(speed), Some(bearing), speed > SUSPECT_JUMP_MPS)
+                }
+            }
+            None => (None, None, false),
+        };
+
+        cache.insert(
+            vehicle_id,
+            Snapshot {
+                lat,
+                lon,
+                timestamp,
+                bearing,
+            },
+        );
+
+        speeds.push(speed);
+        bearings.push(bearing);
+        suspect_jumps.push(suspect);
+    }
+
+    df.hstack(&[
+        Column::new("computed_speed_mps".into(), speeds),
+        Column::new("computed_bearing_deg".into(), bearings),
+        Column::new("suspect_jump".into(), suspect_jumps),
+    ])
+}
@@ -1,19 +1,72 @@
 use std::error::Error;
+use std::fmt;
 use std::io::Cursor;
-use std::time::Instant;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 use curl::easy::Easy;
 use polars::prelude::*;
 use protobuf::Message;
 use rayon::prelude::*;
 
+mod daemon;
+mod gtfs_static;
+mod kinematics;
+mod storage;
 mod protos {
     include!(concat!(env!("OUT_DIR"), "/protos/mod.rs"));
 }
 
 use protos::gtfs_realtime::*;
 
-fn fetch_ztm_data(file: &str) -> Result<Vec<u8>, curl::Error> {
+/// Root directory for the partitioned Parquet time-series store.
+const STORAGE_BASE_DIR: &str = "data";
+
+/// Default polling interval for `--daemon` mode, in seconds.
+const DEFAULT_POLL_INTERVAL_SECS: u64 = 60;
+
+/// How long a downloaded static GTFS zip is reused before refetching. The
+/// schedule itself only changes on publisher rollouts (days/weeks apart), so
+/// there's no reason to re-download a multi-MB zip every ~60s poll cycle —
+/// `gtfs_static::load_static_feed`'s `feed_version` cache only avoids
+/// reparsing, not redownloading.
+const STATIC_GTFS_REFRESH_INTERVAL: Duration = Duration::from_secs(3600);
+
+#[derive(Debug)]
+enum FetchError {
+    Curl(curl::Error),
+    Http(u32),
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FetchError::Curl(e) => write!(f, "transport error: {e}"),
+            FetchError::Http(code) => write!(f, "HTTP error: {code}"),
+        }
+    }
+}
+
+impl Error for FetchError {}
+
+impl From<curl::Error> for FetchError {
+    fn from(e: curl::Error) -> Self {
+        FetchError::Curl(e)
+    }
+}
+
+/// Picks the Polish translation out of a `TranslatedString`, falling back to
+/// whichever translation comes first when no `pl` entry is present.
+fn translated_text(ts: Option<&TranslatedString>, lang: &str) -> Option<String> {
+    let ts = ts?;
+    ts.translation
+        .iter()
+        .find(|t| t.language.as_deref() == Some(lang))
+        .or_else(|| ts.translation.first())
+        .map(|t| t.text().to_string())
+}
+
+fn fetch_ztm_data(file: &str) -> Result<Vec<u8>, FetchError> {
     let url = format!("https://www.ztm.poznan.pl/pl/dla-deweloperow/getGtfsRtFile?file={file}");
     let mut data = Vec::new();
     let mut easy = Easy::new();
@@ -27,10 +80,62 @@ fn fetch_ztm_data(file: &str) -> Result<Vec<u8>, curl::Error> {
         })?;
         transfer.perform()?;
     }
+
+    let status = easy.response_code()?;
+    if !(200..300).contains(&status) {
+        return Err(FetchError::Http(status));
+    }
     Ok(data)
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
+fn fetch_ztm_static_gtfs() -> Result<Vec<u8>, FetchError> {
+    let mut data = Vec::new();
+    let mut easy = Easy::new();
+    easy.url("https://www.ztm.poznan.pl/pl/dla-deweloperow/getGTFSFile")?;
+
+    {
+        let mut transfer = easy.transfer();
+        transfer.write_function(|newdata| {
+            data.extend_from_slice(newdata);
+            Ok(newdata.len())
+        })?;
+        transfer.perform()?;
+    }
+
+    let status = easy.response_code()?;
+    if !(200..300).contains(&status) {
+        return Err(FetchError::Http(status));
+    }
+    Ok(data)
+}
+
+static STATIC_GTFS_CACHE: Mutex<Option<(Instant, Vec<u8>)>> = Mutex::new(None);
+
+/// Wraps `fetch_ztm_static_gtfs` with a time-gated cache so the multi-MB zip
+/// is only redownloaded every `STATIC_GTFS_REFRESH_INTERVAL`, not on every
+/// processing cycle.
+fn fetch_ztm_static_gtfs_cached() -> Result<Vec<u8>, FetchError> {
+    {
+        let cache = STATIC_GTFS_CACHE.lock().unwrap();
+        if let Some((fetched_at, bytes)) = cache.as_ref() {
+            if fetched_at.elapsed() < STATIC_GTFS_REFRESH_INTERVAL {
+                return Ok(bytes.clone());
+            }
+        }
+    }
+
+    let bytes = fetch_ztm_static_gtfs()?;
+    *STATIC_GTFS_CACHE.lock().unwrap() = Some((Instant::now(), bytes.clone()));
+    Ok(bytes)
+}
+
+/// Runs one fetch-parse-enrich-persist pass over all four ZTM files.
+///
+/// Skips the expensive parsing/enrichment/persistence work (returning
+/// `Ok(false)`) when the feed's `header.timestamp` matches
+/// `last_feed_timestamp`, i.e. the publisher hasn't rolled out a new feed
+/// since the previous cycle.
+fn process_cycle(last_feed_timestamp: &mut Option<u64>) -> Result<bool, Box<dyn Error>> {
     let files = [
         "feeds.pb",
         "trip_updates.pb",
@@ -45,155 +150,325 @@ fn main() -> Result<(), Box<dyn Error>> {
     let time1e = time1.elapsed();
 
     let (feeds, trip_updates, vehicle_positions, vehicle_dictionary) = (&d[0], &d[1], &d[2], &d[3]);
+
+    let feed_timestamp = FeedMessage::parse_from_bytes(feeds)
+        .ok()
+        .and_then(|f| f.header.timestamp);
+    if feed_timestamp.is_some() && feed_timestamp == *last_feed_timestamp {
+        println!("Feed timestamp unchanged ({feed_timestamp:?}); skipping this cycle");
+        return Ok(false);
+    }
+
     let time2 = Instant::now();
 
     let vehicle_dictionary_df = CsvReader::new(Cursor::new(vehicle_dictionary))
         .with_options(CsvReadOptions::default().with_infer_schema_length(None))
         .finish()?;
 
-    let feeds_df = match FeedMessage::parse_from_bytes(feeds) {
+    let (feeds_df, alerts_df) = match FeedMessage::parse_from_bytes(feeds) {
         Ok(feed) => {
             let mut entity_ids = Vec::new();
             let mut has_trip_update = Vec::new();
             let mut has_vehicle_position = Vec::new();
             let mut has_alert = Vec::new();
 
+            let mut alert_entity_ids = Vec::new();
+            let mut alert_causes = Vec::new();
+            let mut alert_effects = Vec::new();
+            let mut alert_header_texts = Vec::new();
+            let mut alert_description_texts = Vec::new();
+            let mut alert_urls = Vec::new();
+            let mut alert_starts: Vec<Option<i64>> = Vec::new();
+            let mut alert_ends: Vec<Option<i64>> = Vec::new();
+            let mut alert_route_ids = Vec::new();
+            let mut alert_stop_ids = Vec::new();
+            let mut alert_trip_ids = Vec::new();
+            let mut alert_agency_ids = Vec::new();
+
             for entity in &feed.entity {
                 entity_ids.push(entity.id().to_string());
                 has_trip_update.push(entity.trip_update.is_some());
                 has_vehicle_position.push(entity.vehicle.is_some());
                 has_alert.push(entity.alert.is_some());
+
+                if let Some(alert) = entity.alert.as_ref() {
+                    let cause = format!("{:?}", alert.cause());
+                    let effect = format!("{:?}", alert.effect());
+                    let header_text = translated_text(alert.header_text.as_ref(), "pl");
+                    let description_text = translated_text(alert.description_text.as_ref(), "pl");
+                    let url = translated_text(alert.url.as_ref(), "pl");
+                    let (start, end) = match alert.active_period.first() {
+                        Some(range) => (range.start.map(|v| v as i64), range.end.map(|v| v as i64)),
+                        None => (None, None),
+                    };
+
+                    let mut push_row =
+                        |route_id: Option<String>,
+                         stop_id: Option<String>,
+                         trip_id: Option<String>,
+                         agency_id: Option<String>| {
+                            alert_entity_ids.push(entity.id().to_string());
+                            alert_causes.push(cause.clone());
+                            alert_effects.push(effect.clone());
+                            alert_header_texts.push(header_text.clone());
+                            alert_description_texts.push(description_text.clone());
+                            alert_urls.push(url.clone());
+                            alert_starts.push(start);
+                            alert_ends.push(end);
+                            alert_route_ids.push(route_id);
+                            alert_stop_ids.push(stop_id);
+                            alert_trip_ids.push(trip_id);
+                            alert_agency_ids.push(agency_id);
+                        };
+
+                    if alert.informed_entity.is_empty() {
+                        push_row(None, None, None, None);
+                    } else {
+                        for informed in &alert.informed_entity {
+                            push_row(
+                                informed.route_id.clone(),
+                                informed.stop_id.clone(),
+                                informed.trip.as_ref().and_then(|t| t.trip_id.clone()),
+                                informed.agency_id.clone(),
+                            );
+                        }
+                    }
+                }
             }
 
-            df!(
+            let feeds_df = df!(
                 "entity_id" => entity_ids,
                 "has_trip_update" => has_trip_update,
                 "has_vehicle_position" => has_vehicle_position,
                 "has_alert" => has_alert,
-            )?
+            )?;
+
+            let alerts_df = df!(
+                "entity_id" => alert_entity_ids,
+                "cause" => alert_causes,
+                "effect" => alert_effects,
+                "header_text" => alert_header_texts,
+                "description_text" => alert_description_texts,
+                "url" => alert_urls,
+                "start" => alert_starts,
+                "end" => alert_ends,
+                "route_id" => alert_route_ids,
+                "stop_id" => alert_stop_ids,
+                "trip_id" => alert_trip_ids,
+                "agency_id" => alert_agency_ids,
+            )?;
+
+            (feeds_df, alerts_df)
         }
         Err(e) => {
             eprintln!("Failed to parse feeds: {}", e);
-            df!("error" => ["Failed to parse feeds"])?
+            (
+                df!("error" => ["Failed to parse feeds"])?,
+                df!("error" => ["Failed to parse feeds"])?,
+            )
         }
     };
 
-    let trip_updates_df = match FeedMessage::parse_from_bytes(trip_updates) {
-        Ok(feed) => {
-            let mut entity_ids = Vec::new();
-            let mut trip_ids = Vec::new();
-            let mut route_ids = Vec::new();
-            let mut start_times = Vec::new();
-            let mut start_dates = Vec::new();
-            let mut num_stop_updates = Vec::new();
+    let (trip_updates_df, stop_time_updates_df, trip_updates_timestamp, trip_updates_parsed) =
+        match FeedMessage::parse_from_bytes(trip_updates) {
+            Ok(feed) => {
+                let timestamp = feed.header.timestamp.unwrap_or(0) as i64;
+                let mut entity_ids = Vec::new();
+                let mut trip_ids = Vec::new();
+                let mut route_ids = Vec::new();
+                let mut start_times = Vec::new();
+                let mut start_dates = Vec::new();
+                let mut num_stop_updates = Vec::new();
 
-            for entity in &feed.entity {
-                if entity.trip_update.is_some() {
-                    let trip_update = entity.trip_update.as_ref().unwrap();
-                    entity_ids.push(entity.id().to_string());
-
-                    if trip_update.trip.is_some() {
-                        let trip = trip_update.trip.as_ref().unwrap();
-                        trip_ids.push(trip.trip_id.clone().unwrap_or_default());
-                        route_ids.push(trip.route_id.clone().unwrap_or_default());
-                        start_times.push(trip.start_time.clone().unwrap_or_default());
-                        start_dates.push(trip.start_date.clone().unwrap_or_default());
-                    } else {
-                        trip_ids.push(String::new());
-                        route_ids.push(String::new());
-                        start_times.push(String::new());
-                        start_dates.push(String::new());
-                    }
+                let mut stu_entity_ids = Vec::new();
+                let mut stu_trip_ids = Vec::new();
+                let mut stu_route_ids = Vec::new();
+                let mut stu_stop_ids = Vec::new();
+                let mut stu_stop_sequences: Vec<Option<i64>> = Vec::new();
+                let mut stu_arrival_delays: Vec<Option<i32>> = Vec::new();
+                let mut stu_departure_delays: Vec<Option<i32>> = Vec::new();
+                let mut stu_arrival_times: Vec<Option<i64>> = Vec::new();
+                let mut stu_departure_times: Vec<Option<i64>> = Vec::new();
+                let mut stu_schedule_relationships = Vec::new();
 
-                    num_stop_updates.push(trip_update.stop_time_update.len() as i64);
+                for entity in &feed.entity {
+                    if entity.trip_update.is_some() {
+                        let trip_update = entity.trip_update.as_ref().unwrap();
+                        entity_ids.push(entity.id().to_string());
+
+                        let (trip_id, route_id) = if trip_update.trip.is_some() {
+                            let trip = trip_update.trip.as_ref().unwrap();
+                            trip_ids.push(trip.trip_id.clone().unwrap_or_default());
+                            route_ids.push(trip.route_id.clone().unwrap_or_default());
+                            start_times.push(trip.start_time.clone().unwrap_or_default());
+                            start_dates.push(trip.start_date.clone().unwrap_or_default());
+                            (
+                                trip.trip_id.clone().unwrap_or_default(),
+                                trip.route_id.clone().unwrap_or_default(),
+                            )
+                        } else {
+                            trip_ids.push(String::new());
+                            route_ids.push(String::new());
+                            start_times.push(String::new());
+                            start_dates.push(String::new());
+                            (String::new(), String::new())
+                        };
+
+                        num_stop_updates.push(trip_update.stop_time_update.len() as i64);
+
+                        for stu in &trip_update.stop_time_update {
+                            stu_entity_ids.push(entity.id().to_string());
+                            stu_trip_ids.push(trip_id.clone());
+                            stu_route_ids.push(route_id.clone());
+                            stu_stop_ids.push(stu.stop_id.clone().unwrap_or_default());
+                            stu_stop_sequences.push(stu.stop_sequence.map(|v| v as i64));
+                            stu_arrival_delays.push(stu.arrival.as_ref().and_then(|e| e.delay));
+                            stu_departure_delays.push(stu.departure.as_ref().and_then(|e| e.delay));
+                            stu_arrival_times.push(stu.arrival.as_ref().and_then(|e| e.time));
+                            stu_departure_times.push(stu.departure.as_ref().and_then(|e| e.time));
+                            stu_schedule_relationships
+                                .push(format!("{:?}", stu.schedule_relationship()));
+                        }
+                    }
                 }
+
+                let trip_updates_df = df!(
+                    "entity_id" => entity_ids,
+                    "trip_id" => trip_ids,
+                    "route_id" => route_ids,
+                    "start_time" => start_times,
+                    "start_date" => start_dates,
+                    "num_stop_updates" => num_stop_updates,
+                )?;
+
+                let stop_time_updates_df = df!(
+                    "entity_id" => stu_entity_ids,
+                    "trip_id" => stu_trip_ids,
+                    "route_id" => stu_route_ids,
+                    "stop_id" => stu_stop_ids,
+                    "stop_sequence" => stu_stop_sequences,
+                    "arrival_delay" => stu_arrival_delays,
+                    "departure_delay" => stu_departure_delays,
+                    "arrival_time" => stu_arrival_times,
+                    "departure_time" => stu_departure_times,
+                    "schedule_relationship" => stu_schedule_relationships,
+                )?;
+
+                (trip_updates_df, stop_time_updates_df, timestamp, true)
+            }
+            Err(e) => {
+                eprintln!("Failed to parse trip updates: {}", e);
+                (
+                    df!("error" => ["Failed to parse trip updates"])?,
+                    df!("error" => ["Failed to parse trip updates"])?,
+                    0,
+                    false,
+                )
             }
+        };
 
-            df!(
-                "entity_id" => entity_ids,
-                "trip_id" => trip_ids,
-                "route_id" => route_ids,
-                "start_time" => start_times,
-                "start_date" => start_dates,
-                "num_stop_updates" => num_stop_updates,
-            )?
-        }
-        Err(e) => {
-            eprintln!("Failed to parse trip updates: {}", e);
-            df!("error" => ["Failed to parse trip updates"])?
-        }
-    };
+    let (vehicle_positions_df, vehicle_positions_timestamp, vehicle_positions_parsed) =
+        match FeedMessage::parse_from_bytes(vehicle_positions) {
+            Ok(feed) => {
+                let timestamp = feed.header.timestamp.unwrap_or(0) as i64;
+                let mut entity_ids = Vec::new();
+                let mut vehicle_ids = Vec::new();
+                let mut vehicle_labels = Vec::new();
+                let mut latitudes = Vec::new();
+                let mut longitudes = Vec::new();
+                let mut bearings = Vec::new();
+                let mut speeds = Vec::new();
+                let mut trip_ids = Vec::new();
+                let mut route_ids = Vec::new();
 
-    let vehicle_positions_df = match FeedMessage::parse_from_bytes(vehicle_positions) {
-        Ok(feed) => {
-            let mut entity_ids = Vec::new();
-            let mut vehicle_ids = Vec::new();
-            let mut vehicle_labels = Vec::new();
-            let mut latitudes = Vec::new();
-            let mut longitudes = Vec::new();
-            let mut bearings = Vec::new();
-            let mut speeds = Vec::new();
-            let mut trip_ids = Vec::new();
-            let mut route_ids = Vec::new();
+                for entity in &feed.entity {
+                    if entity.vehicle.is_some() {
+                        let vehicle = entity.vehicle.as_ref().unwrap();
+                        entity_ids.push(entity.id().to_string());
 
-            for entity in &feed.entity {
-                if entity.vehicle.is_some() {
-                    let vehicle = entity.vehicle.as_ref().unwrap();
-                    entity_ids.push(entity.id().to_string());
-
-                    if vehicle.vehicle.is_some() {
-                        let veh_desc = vehicle.vehicle.as_ref().unwrap();
-                        vehicle_ids.push(veh_desc.id.clone().unwrap_or_default());
-                        vehicle_labels.push(veh_desc.label.clone().unwrap_or_default());
-                    } else {
-                        vehicle_ids.push(String::new());
-                        vehicle_labels.push(String::new());
-                    }
+                        if vehicle.vehicle.is_some() {
+                            let veh_desc = vehicle.vehicle.as_ref().unwrap();
+                            vehicle_ids.push(veh_desc.id.clone().unwrap_or_default());
+                            vehicle_labels.push(veh_desc.label.clone().unwrap_or_default());
+                        } else {
+                            vehicle_ids.push(String::new());
+                            vehicle_labels.push(String::new());
+                        }
 
-                    if vehicle.position.is_some() {
-                        let pos = vehicle.position.as_ref().unwrap();
-                        latitudes.push(pos.latitude());
-                        longitudes.push(pos.longitude());
-                        bearings.push(pos.bearing.unwrap_or(0.0));
-                        speeds.push(pos.speed.unwrap_or(0.0));
-                    } else {
-                        latitudes.push(0.0);
-                        longitudes.push(0.0);
-                        bearings.push(0.0);
-                        speeds.push(0.0);
-                    }
+                        if vehicle.position.is_some() {
+                            let pos = vehicle.position.as_ref().unwrap();
+                            latitudes.push(pos.latitude());
+                            longitudes.push(pos.longitude());
+                            bearings.push(pos.bearing.unwrap_or(0.0));
+                            speeds.push(pos.speed.unwrap_or(0.0));
+                        } else {
+                            latitudes.push(0.0);
+                            longitudes.push(0.0);
+                            bearings.push(0.0);
+                            speeds.push(0.0);
+                        }
 
-                    if vehicle.trip.is_some() {
-                        let trip = vehicle.trip.as_ref().unwrap();
-                        trip_ids.push(trip.trip_id.clone().unwrap_or_default());
-                        route_ids.push(trip.route_id.clone().unwrap_or_default());
-                    } else {
-                        trip_ids.push(String::new());
-                        route_ids.push(String::new());
+                        if vehicle.trip.is_some() {
+                            let trip = vehicle.trip.as_ref().unwrap();
+                            trip_ids.push(trip.trip_id.clone().unwrap_or_default());
+                            route_ids.push(trip.route_id.clone().unwrap_or_default());
+                        } else {
+                            trip_ids.push(String::new());
+                            route_ids.push(String::new());
+                        }
                     }
                 }
+
+                let df = df!(
+                    "entity_id" => entity_ids,
+                    "vehicle_id" => vehicle_ids,
+                    "vehicle_label" => vehicle_labels,
+                    "latitude" => latitudes,
+                    "longitude" => longitudes,
+                    "bearing" => bearings,
+                    "speed" => speeds,
+                    "trip_id" => trip_ids,
+                    "route_id" => route_ids,
+                )?;
+
+                (df, timestamp, true)
+            }
+            Err(e) => {
+                eprintln!("Failed to parse vehicle positions: {}", e);
+                (
+                    df!("error" => ["Failed to parse vehicle positions"])?,
+                    0,
+                    false,
+                )
             }
+        };
 
-            df!(
-                "entity_id" => entity_ids,
-                "vehicle_id" => vehicle_ids,
-                "vehicle_label" => vehicle_labels,
-                "latitude" => latitudes,
-                "longitude" => longitudes,
-                "bearing" => bearings,
-                "speed" => speeds,
-                "trip_id" => trip_ids,
-                "route_id" => route_ids,
-            )?
-        }
-        Err(e) => {
-            eprintln!("Failed to parse vehicle positions: {}", e);
-            df!("error" => ["Failed to parse vehicle positions"])?
-        }
-    };
+    let vehicle_positions_df =
+        kinematics::compute_motion(vehicle_positions_df, vehicle_positions_timestamp)?;
 
     let time2e = time2.elapsed();
 
+    // Persisted to the Parquet store pre-enrichment, so the partition's schema
+    // stays stable even on cycles where the static GTFS fetch/join fails.
+    let vehicle_positions_store_df = vehicle_positions_df.clone();
+    let stop_time_updates_store_df = stop_time_updates_df.clone();
+
+    let (vehicle_positions_df, trip_updates_df, stop_time_updates_df) =
+        match fetch_ztm_static_gtfs_cached()
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| gtfs_static::load_static_feed(&bytes).map_err(|e| e.to_string()))
+        {
+            Ok(static_feed) => (
+                gtfs_static::enrich_with_route_and_trip(vehicle_positions_df, &static_feed)?,
+                gtfs_static::enrich_with_route_and_trip(trip_updates_df, &static_feed)?,
+                gtfs_static::enrich_stop_time_updates(stop_time_updates_df, &static_feed)?,
+            ),
+            Err(e) => {
+                eprintln!("Failed to load static GTFS schedule: {}", e);
+                (vehicle_positions_df, trip_updates_df, stop_time_updates_df)
+            }
+        };
+
     println!("\n=== Vehicle Dictionary ===");
     println!("{}", vehicle_dictionary_df);
     println!(
@@ -216,6 +491,17 @@ fn main() -> Result<(), Box<dyn Error>> {
             .collect::<Vec<String>>()
     );
 
+    println!("\n=== Alerts ===");
+    println!("{}", alerts_df);
+    println!(
+        "{:?}",
+        alerts_df
+            .get_columns()
+            .iter()
+            .map(|c| c.name().to_string())
+            .collect::<Vec<String>>()
+    );
+
     println!("\n=== Trip Updates ===");
     println!("{}", trip_updates_df);
     println!(
@@ -227,6 +513,17 @@ fn main() -> Result<(), Box<dyn Error>> {
             .collect::<Vec<String>>()
     );
 
+    println!("\n=== Stop Time Updates ===");
+    println!("{}", stop_time_updates_df);
+    println!(
+        "{:?}",
+        stop_time_updates_df
+            .get_columns()
+            .iter()
+            .map(|c| c.name().to_string())
+            .collect::<Vec<String>>()
+    );
+
     println!("\n=== Vehicle Positions ===");
     println!("{}", vehicle_positions_df);
     println!(
@@ -239,8 +536,53 @@ fn main() -> Result<(), Box<dyn Error>> {
     );
     println!("{:?}", vehicle_positions_df.column("speed")?.mean_reduce());
 
+    if vehicle_positions_parsed {
+        if let Err(e) = storage::append_snapshot(
+            &vehicle_positions_store_df,
+            STORAGE_BASE_DIR,
+            "vehicle_positions",
+            vehicle_positions_timestamp,
+            &["entity_id"],
+        ) {
+            eprintln!("Failed to persist vehicle positions snapshot: {}", e);
+        }
+    }
+    if trip_updates_parsed {
+        if let Err(e) = storage::append_snapshot(
+            &stop_time_updates_store_df,
+            STORAGE_BASE_DIR,
+            "stop_time_updates",
+            trip_updates_timestamp,
+            &["entity_id", "stop_id", "stop_sequence"],
+        ) {
+            eprintln!("Failed to persist stop time updates snapshot: {}", e);
+        }
+    }
+
     println!("TIME SPENT DOWNLOADING DATA = {:?}", time1e);
     println!("TIME SPENT CONSTRUCTING DATA = {:?}", time2e);
 
-    Ok(())
+    *last_feed_timestamp = feed_timestamp;
+    Ok(true)
+}
+
+/// Parses `--daemon` (run forever, polling on an interval) and
+/// `--interval <seconds>` (default `DEFAULT_POLL_INTERVAL_SECS`) out of the
+/// process arguments. Without `--daemon` a single cycle is run and the
+/// process exits, matching the tool's original one-shot behaviour.
+fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = std::env::args().collect();
+    let daemon_mode = args.iter().any(|a| a == "--daemon");
+    let interval_secs = args
+        .iter()
+        .position(|a| a == "--interval")
+        .and_then(|i| args.get(i + 1))
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_POLL_INTERVAL_SECS);
+
+    if daemon_mode {
+        daemon::run(Duration::from_secs(interval_secs), process_cycle)
+    } else {
+        process_cycle(&mut None).map(|_| ())
+    }
 }
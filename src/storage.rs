@@ -0,0 +1,59 @@
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use polars::prelude::*;
+
+/// Stamps `df` with a `snapshot_ts` column (the feed's `header.timestamp`)
+/// and appends it to a Parquet file under `base_dir`, partitioned by the
+/// UTC date the snapshot falls on, deduplicating on `dedup_keys` (plus
+/// `snapshot_ts`) so re-polling the same feed timestamp doesn't duplicate
+/// rows. `dedup_keys` must identify one row of `df`: `["entity_id"]` for a
+/// one-row-per-entity frame like `vehicle_positions_df`, or
+/// `["entity_id", "stop_id", "stop_sequence"]` for a long-format frame like
+/// `stop_time_updates_df` that has many rows per entity — include `stop_id`
+/// alongside `stop_sequence` since GTFS-rt allows the latter to be absent,
+/// and Polars' `unique()` treats two null `stop_sequence`s as equal.
+pub fn append_snapshot(
+    df: &DataFrame,
+    base_dir: &str,
+    name: &str,
+    snapshot_ts: i64,
+    dedup_keys: &[&str],
+) -> Result<(), Box<dyn Error>> {
+    let mut df = df.clone();
+    df.with_column(Series::new(
+        "snapshot_ts".into(),
+        vec![snapshot_ts; df.height()],
+    ))?;
+
+    let date = DateTime::<Utc>::from_timestamp(snapshot_ts, 0)
+        .unwrap_or_else(|| DateTime::<Utc>::from_timestamp(0, 0).unwrap())
+        .format("%Y-%m-%d");
+
+    let partition_dir = Path::new(base_dir).join(format!("date={date}"));
+    fs::create_dir_all(&partition_dir)?;
+    let path = partition_dir.join(format!("{name}.parquet"));
+
+    let mut unique_keys: Vec<String> = dedup_keys.iter().map(|k| k.to_string()).collect();
+    unique_keys.push("snapshot_ts".to_string());
+
+    let merged = if path.exists() {
+        let existing = ParquetReader::new(fs::File::open(&path)?).finish()?;
+        concat([existing.lazy(), df.lazy()], UnionArgs::default())?
+            .unique(Some(unique_keys), UniqueKeepStrategy::Last)
+            .collect()?
+    } else {
+        df
+    };
+
+    write_parquet(&merged, &path)
+}
+
+fn write_parquet(df: &DataFrame, path: &PathBuf) -> Result<(), Box<dyn Error>> {
+    let mut df = df.clone();
+    let file = fs::File::create(path)?;
+    ParquetWriter::new(file).finish(&mut df)?;
+    Ok(())
+}